@@ -1,4 +1,5 @@
-use std::cell::OnceCell;
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::ops::{Index, IndexMut};
 
@@ -12,8 +13,9 @@ use crate::theme::Theme;
 pub struct Bar {
     /// The actual bar items - represents the latest state of each individual bar item
     items: Vec<I3Item>,
-    /// Cache for the adjuster for the dim fg theme colour
-    dim_adjuster: OnceCell<Box<dyn Fn(&HexColor) -> HexColor>>,
+    /// Cache for the contrast-adjusted dim fg theme colour, keyed by the `item_bg` it was
+    /// computed against since different items can have different backgrounds
+    dim_adjuster: OnceCell<RefCell<HashMap<HexColor, HexColor>>>,
 }
 
 impl Debug for Bar {
@@ -135,12 +137,14 @@ impl Bar {
                 }
             }
 
-            // replace `config.theme.dim` so it's easy to see
+            // replace `config.theme.dim` so it's easy to see against this item's background
             let adjusted_dim = self
                 .dim_adjuster
-                .get_or_init(|| Box::new(make_color_adjuster(&theme.bg, &theme.dim)))(
-                &item_bg
-            );
+                .get_or_init(|| RefCell::new(HashMap::new()))
+                .borrow_mut()
+                .entry(item_bg)
+                .or_insert_with(|| ensure_contrast(&theme.dim, &item_bg, &theme.bg, theme.min_contrast))
+                .to_owned();
 
             powerline_bar.push(sep_item);
             powerline_bar.push(
@@ -171,17 +175,117 @@ impl Bar {
     }
 }
 
-/// HACK: this assumes that RGB colours scale linearly - I don't know if they do or not.
-/// Used to render the powerline bar and make sure that dim text is visible.
-fn make_color_adjuster(bg: &HexColor, fg: &HexColor) -> impl Fn(&HexColor) -> HexColor {
-    let r = fg.r.abs_diff(bg.r);
-    let g = fg.g.abs_diff(bg.g);
-    let b = fg.b.abs_diff(bg.b);
-    move |c| {
-        HexColor::rgb(
-            r.saturating_add(c.r),
-            g.saturating_add(c.g),
-            b.saturating_add(c.b),
-        )
+/// Linearize a single sRGB channel (as defined by the WCAG 2.x contrast formula)
+fn linearize_channel(c: u8) -> f64 {
+    let c = c as f64 / 255.0;
+    if c <= 0.03928 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// WCAG relative luminance of a colour, in `[0, 1]`
+fn relative_luminance(color: &HexColor) -> f64 {
+    0.2126 * linearize_channel(color.r)
+        + 0.7152 * linearize_channel(color.g)
+        + 0.0722 * linearize_channel(color.b)
+}
+
+/// WCAG contrast ratio between two colours, in `[1, 21]`
+fn contrast_ratio(a: &HexColor, b: &HexColor) -> f64 {
+    let (l1, l2) = (relative_luminance(a), relative_luminance(b));
+    let (lighter, darker) = if l1 >= l2 { (l1, l2) } else { (l2, l1) };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+/// Linearly blend two colours: `t = 0.0` returns `from`, `t = 1.0` returns `to`
+fn blend(from: &HexColor, to: &HexColor, t: f64) -> HexColor {
+    let lerp = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    HexColor::rgb(lerp(from.r, to.r), lerp(from.g, to.g), lerp(from.b, to.b))
+}
+
+/// Used to render the powerline bar and make sure that `fg` (typically `theme.dim`) stays
+/// readable against an arbitrary `item_bg`. If the contrast ratio between the two is below
+/// `min_contrast`, nudge `fg` towards whichever of `bg`/white has the higher contrast against
+/// `item_bg`, blending in fixed steps until the ratio is met (or we run out of room to blend).
+fn ensure_contrast(fg: &HexColor, item_bg: &HexColor, bg: &HexColor, min_contrast: f64) -> HexColor {
+    if contrast_ratio(fg, item_bg) >= min_contrast {
+        return *fg;
+    }
+
+    let white = HexColor::WHITE;
+    let target = if contrast_ratio(&white, item_bg) >= contrast_ratio(bg, item_bg) {
+        &white
+    } else {
+        bg
+    };
+
+    const STEPS: u32 = 20;
+    let mut best = *fg;
+    for step in 1..=STEPS {
+        let candidate = blend(fg, target, step as f64 / STEPS as f64);
+        best = candidate;
+        if contrast_ratio(&candidate, item_bg) >= min_contrast {
+            break;
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn black() -> HexColor {
+        HexColor::rgb(0, 0, 0)
+    }
+
+    fn white() -> HexColor {
+        HexColor::rgb(255, 255, 255)
+    }
+
+    #[test]
+    fn relative_luminance_of_black_and_white() {
+        assert_eq!(relative_luminance(&black()), 0.0);
+        assert_eq!(relative_luminance(&white()), 1.0);
+    }
+
+    #[test]
+    fn contrast_ratio_black_on_white_is_maximal() {
+        assert_eq!(contrast_ratio(&black(), &white()), 21.0);
+        // order shouldn't matter
+        assert_eq!(contrast_ratio(&white(), &black()), 21.0);
+    }
+
+    #[test]
+    fn contrast_ratio_of_a_colour_with_itself_is_one() {
+        let grey = HexColor::rgb(128, 128, 128);
+        assert_eq!(contrast_ratio(&grey, &grey), 1.0);
+    }
+
+    #[test]
+    fn blend_at_extremes_returns_the_endpoints() {
+        let grey = HexColor::rgb(128, 128, 128);
+        assert_eq!(blend(&black(), &grey, 0.0), black());
+        assert_eq!(blend(&black(), &grey, 1.0), grey);
+    }
+
+    #[test]
+    fn ensure_contrast_leaves_already_readable_colours_alone() {
+        // black on white is already well above any reasonable threshold
+        assert_eq!(ensure_contrast(&black(), &white(), &black(), 4.5), black());
+    }
+
+    #[test]
+    fn ensure_contrast_nudges_low_contrast_colours_towards_the_threshold() {
+        // a dim grey on a similarly grey background starts out unreadable
+        let dim = HexColor::rgb(120, 120, 120);
+        let item_bg = HexColor::rgb(100, 100, 100);
+        assert!(contrast_ratio(&dim, &item_bg) < 4.5);
+
+        let adjusted = ensure_contrast(&dim, &item_bg, &black(), 4.5);
+        assert!(contrast_ratio(&adjusted, &item_bg) >= 4.5);
     }
 }
@@ -0,0 +1,379 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use serde_derive::{Deserialize, Serialize};
+use zbus::fdo::DBusProxy;
+use zbus::Connection;
+
+use crate::context::{BarEvent, BarItem, Context};
+use crate::dbus::dbus_connection;
+use crate::dbus::mpris::{
+    track_from_metadata, MediaPlayer2PlayerProxy, PlaybackStatus, BUS_NAME_PREFIX,
+};
+use crate::dbus::subscription::{subscribe, DbusEvent, SubscriptionSpec};
+use crate::dbus::BusType;
+use crate::exec::exec;
+use crate::i3::{I3Item, I3Markup};
+use crate::theme::Theme;
+
+/// Watch `org.freedesktop.DBus` for players appearing/disappearing on the bus
+fn watch_name_owner_changes() -> BoxStream<'static, DbusEvent> {
+    subscribe(vec![SubscriptionSpec::new(
+        BusType::Session,
+        "org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+        "org.freedesktop.DBus",
+        "NameOwnerChanged",
+    )])
+}
+
+/// Watch a single player's `PropertiesChanged` (track metadata, `PlaybackStatus`, ...)
+fn watch_player_properties(name: &str) -> BoxStream<'static, DbusEvent> {
+    subscribe(vec![SubscriptionSpec::new(
+        BusType::Session,
+        name,
+        "/org/mpris/MediaPlayer2",
+        "org.freedesktop.DBus.Properties",
+        "PropertiesChanged",
+    )])
+}
+
+/// Find every `org.mpris.MediaPlayer2.*` name currently on the session bus, in bus order.
+async fn list_players(dbus: &DBusProxy<'_>) -> zbus::Result<Vec<String>> {
+    Ok(dbus
+        .list_names()
+        .await?
+        .into_iter()
+        .map(String::from)
+        .filter(|name| name.starts_with(BUS_NAME_PREFIX))
+        .collect())
+}
+
+/// A successfully-resolved candidate player, kept separate from its (unresolvable-from-a-test)
+/// `MediaPlayer2PlayerProxy` so the selection logic in [`rank_candidates`] stays pure
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Candidate {
+    name: String,
+    status: PlaybackStatus,
+}
+
+/// Rank already-resolved `candidates` and return the index of the winner, according to `prefer`.
+/// A player that's actually `Playing` always wins over one that's merely `Paused`; `prefer` only
+/// breaks ties between players in the same state.
+///
+/// Candidates that failed to resolve (e.g. a player vanished mid-enumeration) are simply absent
+/// from `candidates` by the time this runs - this must keep picking a winner from whatever's
+/// left rather than being thrown off by the gap.
+fn rank_candidates(candidates: &[Candidate], prefer: &[String]) -> Option<usize> {
+    candidates
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, c)| {
+            let state_rank = if c.status == PlaybackStatus::Playing { 0 } else { 1 };
+            let prefer_rank = prefer
+                .iter()
+                .position(|p| c.name == format!("{}{}", BUS_NAME_PREFIX, p))
+                .unwrap_or(prefer.len());
+            (state_rank, prefer_rank)
+        })
+        .map(|(i, _)| i)
+}
+
+/// Pick the active player out of the currently available ones, according to `prefer`.
+async fn pick_player(
+    connection: &Connection,
+    players: &[String],
+    prefer: &[String],
+) -> Option<(String, MediaPlayer2PlayerProxy<'static>)> {
+    let mut candidates = Vec::new();
+    let mut proxies = Vec::new();
+    for name in players {
+        // pass an owned `String` (not `name.as_str()`) so the resulting proxy doesn't borrow
+        // from `players`/`connection` and can be kept around after this function returns, even
+        // once those are reconnected from scratch
+        let Ok(builder) = MediaPlayer2PlayerProxy::builder(connection).destination(name.clone())
+        else {
+            // e.g. the player vanished between `list_players` and here - skip it, don't bail
+            // out of the whole selection
+            continue;
+        };
+        let Ok(proxy) = builder.build().await else {
+            continue;
+        };
+
+        let status = proxy
+            .playback_status()
+            .await
+            .map(|s| PlaybackStatus::from(s.as_str()))
+            .unwrap_or(PlaybackStatus::Stopped);
+
+        candidates.push(Candidate {
+            name: name.clone(),
+            status,
+        });
+        proxies.push(proxy);
+    }
+
+    let winner = rank_candidates(&candidates, prefer)?;
+    Some((candidates.swap_remove(winner).name, proxies.swap_remove(winner)))
+}
+
+/// Whether a `NameOwnerChanged` event for `name` (an empty `new_owner` means the name was
+/// released, i.e. the player quit) should cause us to drop the player we're currently tracking.
+/// An unrelated player appearing/disappearing must never reset a still-healthy `tracked` one.
+fn should_drop_tracked_player(tracked: Option<&str>, name: &str, new_owner: &str) -> bool {
+    let player_left = new_owner.is_empty();
+    player_left && tracked == Some(name)
+}
+
+fn truncate(s: &str, max_length: usize) -> String {
+    if s.chars().count() <= max_length {
+        return s.to_owned();
+    }
+
+    let mut truncated = s.chars().take(max_length.saturating_sub(1)).collect::<String>();
+    truncated.push('…');
+    truncated
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Mpris {
+    /// Preference order of player bus-name suffixes (e.g. `spotify`, `vlc`), used to pick which
+    /// player to show when several players on the bus are in the same state (a `Playing` player
+    /// always wins over a paused or stopped one). An empty list means "whichever player appeared
+    /// first".
+    #[serde(default)]
+    prefer: Vec<String>,
+    /// Truncate `"{artist} - {title}"` to this many characters (including the `…`)
+    #[serde(default = "Mpris::default_max_length")]
+    max_length: usize,
+    /// Command executed on middle click
+    #[serde(default)]
+    exec: Option<String>,
+}
+
+impl Mpris {
+    fn default_max_length() -> usize {
+        40
+    }
+
+    fn render(&self, theme: &Theme, status: PlaybackStatus, artist: &str, title: &str) -> I3Item {
+        let glyph = match status {
+            PlaybackStatus::Playing => "",
+            PlaybackStatus::Paused => "",
+            PlaybackStatus::Stopped => "",
+        };
+
+        let track = if artist.is_empty() {
+            title.to_owned()
+        } else {
+            format!("{} - {}", artist, title)
+        };
+
+        let fg = match status {
+            PlaybackStatus::Playing => theme.green,
+            PlaybackStatus::Paused => theme.dim,
+            PlaybackStatus::Stopped => theme.dim,
+        };
+
+        I3Item::new(format!("{} {}", glyph, truncate(&track, self.max_length)))
+            .color(fg)
+            .markup(I3Markup::Pango)
+    }
+
+    async fn handle_click(&self, event: &BarEvent, player: &MediaPlayer2PlayerProxy<'_>) {
+        let BarEvent::Click(click) = event else {
+            return;
+        };
+
+        let _ = match click.button {
+            1 => player.play_pause().await,
+            4 => player.previous().await,
+            5 => player.next().await,
+            2 => {
+                if let Some(cmd) = &self.exec {
+                    exec(cmd).await;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        };
+    }
+}
+
+#[async_trait(?Send)]
+impl BarItem for Mpris {
+    async fn start(self: Box<Self>, mut ctx: Context) -> Result<(), Box<dyn Error>> {
+        // `subscribe` handles its own reconnection, so this never needs re-establishing even if
+        // the session bus itself bounces
+        let mut name_owner_changed = watch_name_owner_changes();
+
+        let mut player: Option<(String, MediaPlayer2PlayerProxy<'static>)> = None;
+        let mut properties_changed = None;
+
+        loop {
+            if player.is_none() {
+                // re-establish our own bus connection here too (rather than reusing a
+                // `connection`/`dbus` built once at the top of `start`), so a session bus
+                // restart doesn't permanently kill this item
+                if let Ok(connection) = dbus_connection(BusType::Session).await {
+                    if let Ok(dbus) = DBusProxy::new(&connection).await {
+                        if let Ok(players) = list_players(&dbus).await {
+                            player = pick_player(&connection, &players, &self.prefer).await;
+                            properties_changed = player
+                                .as_ref()
+                                .map(|(name, _)| watch_player_properties(name));
+                        }
+                    }
+                }
+            }
+
+            let item = match &player {
+                Some((_, proxy)) => {
+                    let status = proxy
+                        .playback_status()
+                        .await
+                        .map(|s| PlaybackStatus::from(s.as_str()))
+                        .unwrap_or(PlaybackStatus::Stopped);
+                    let metadata = proxy.metadata().await.unwrap_or_default();
+                    match track_from_metadata(&metadata) {
+                        Some((artist, title)) => self.render(&ctx.theme(), status, &artist, &title),
+                        None => I3Item::empty(),
+                    }
+                }
+                None => I3Item::empty(),
+            };
+            ctx.update_item(item).await?;
+
+            tokio::select! {
+                Some(event) = ctx.wait_for_event(None) => {
+                    if let Some((_, proxy)) = &player {
+                        self.handle_click(&event, proxy).await;
+                    }
+                }
+                Some(event) = name_owner_changed.next() => {
+                    let (name, _old_owner, new_owner) = event.message.body::<(String, String, String)>()?;
+                    let tracked = player.as_ref().map(|(n, _)| n.as_str());
+                    if should_drop_tracked_player(tracked, &name, &new_owner) {
+                        player = None;
+                        properties_changed = None;
+                    }
+                }
+                Some(_) = async {
+                    match &mut properties_changed {
+                        Some(stream) => stream.next().await,
+                        None => std::future::pending().await,
+                    }
+                } => continue,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_leaves_short_strings_alone() {
+        assert_eq!(truncate("Outro", 40), "Outro");
+        assert_eq!(truncate("Outro", 5), "Outro");
+    }
+
+    #[test]
+    fn truncate_cuts_long_strings_and_appends_an_ellipsis() {
+        assert_eq!(truncate("Daft Punk - One More Time", 10), "Daft Punk…");
+    }
+
+    #[test]
+    fn truncate_counts_chars_not_bytes() {
+        // multi-byte chars shouldn't be split in the middle
+        assert_eq!(truncate("Susanne Sundfør", 8), "Susanne…");
+        // 15 chars but 16 bytes: fits as-is, and a byte cut would land inside the `ø`
+        assert_eq!(truncate("Susanne Sundfør", 15), "Susanne Sundfør");
+    }
+
+    fn candidate(name: &str, status: PlaybackStatus) -> Candidate {
+        Candidate {
+            name: name.to_owned(),
+            status,
+        }
+    }
+
+    #[test]
+    fn rank_candidates_prefers_playing_over_paused() {
+        let candidates = vec![
+            candidate("org.mpris.MediaPlayer2.vlc", PlaybackStatus::Paused),
+            candidate("org.mpris.MediaPlayer2.spotify", PlaybackStatus::Playing),
+        ];
+        assert_eq!(rank_candidates(&candidates, &[]), Some(1));
+    }
+
+    #[test]
+    fn rank_candidates_breaks_ties_with_prefer_order() {
+        let candidates = vec![
+            candidate("org.mpris.MediaPlayer2.vlc", PlaybackStatus::Paused),
+            candidate("org.mpris.MediaPlayer2.spotify", PlaybackStatus::Paused),
+        ];
+        let prefer = vec!["spotify".to_owned()];
+        assert_eq!(rank_candidates(&candidates, &prefer), Some(1));
+    }
+
+    #[test]
+    fn rank_candidates_skips_a_dead_candidate_without_discarding_the_rest() {
+        // simulates `pick_player` failing to build a proxy for the middle player: it's simply
+        // absent from `candidates`, and the still-healthy, actively-playing one must still win
+        let candidates = vec![
+            candidate("org.mpris.MediaPlayer2.vlc", PlaybackStatus::Paused),
+            candidate("org.mpris.MediaPlayer2.spotify", PlaybackStatus::Playing),
+        ];
+        assert_eq!(rank_candidates(&candidates, &[]), Some(1));
+    }
+
+    #[test]
+    fn rank_candidates_with_no_candidates_is_none() {
+        assert_eq!(rank_candidates(&[], &[]), None);
+    }
+
+    #[test]
+    fn unrelated_player_name_owner_changed_does_not_reset_tracked_player() {
+        let tracked = Some("org.mpris.MediaPlayer2.spotify");
+        assert!(!should_drop_tracked_player(
+            tracked,
+            "org.mpris.MediaPlayer2.vlc",
+            "",
+        ));
+    }
+
+    #[test]
+    fn tracked_player_leaving_resets_it() {
+        let tracked = Some("org.mpris.MediaPlayer2.spotify");
+        assert!(should_drop_tracked_player(
+            tracked,
+            "org.mpris.MediaPlayer2.spotify",
+            "",
+        ));
+    }
+
+    #[test]
+    fn tracked_player_gaining_a_new_owner_is_not_a_departure() {
+        // a non-empty `new_owner` means the name just changed hands, not that it was released
+        let tracked = Some("org.mpris.MediaPlayer2.spotify");
+        assert!(!should_drop_tracked_player(
+            tracked,
+            "org.mpris.MediaPlayer2.spotify",
+            ":1.23",
+        ));
+    }
+
+    #[test]
+    fn no_tracked_player_is_never_reset() {
+        assert!(!should_drop_tracked_player(
+            None,
+            "org.mpris.MediaPlayer2.spotify",
+            "",
+        ));
+    }
+}
@@ -9,8 +9,8 @@ use iwlib::WirelessInfo;
 use serde::{de, Deserialize, Serialize};
 
 use crate::context::{BarItem, Context};
-use crate::dbus::dbus_connection;
-use crate::dbus::network_manager::NetworkManagerProxy;
+use crate::dbus::subscription::{subscribe, SubscriptionSpec};
+use crate::dbus::BusType;
 use crate::format::fraction;
 use crate::i3::{I3Item, I3Markup};
 use crate::net::{Interface, InterfaceKind};
@@ -146,9 +146,16 @@ pub struct Nic {
 #[async_trait(?Send)]
 impl BarItem for Nic {
     async fn start(self: Box<Self>, mut ctx: Context) -> Result<(), Box<dyn Error>> {
-        let connection = dbus_connection(crate::dbus::BusType::System).await?;
-        let nm = NetworkManagerProxy::new(&connection).await?;
-        let mut nm_state_change = nm.receive_state_changed().await?;
+        // NetworkManager doesn't expose link state changes as a property, so we watch its
+        // bespoke `StateChanged` signal via the generic subscription subsystem - this also
+        // means we no longer error out if NetworkManager (re)starts while we're running
+        let mut nm_state_change = subscribe(vec![SubscriptionSpec::new(
+            BusType::System,
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "StateChanged",
+        )]);
 
         let mut idx = 0;
         loop {
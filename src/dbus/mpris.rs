@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use zbus::dbus_proxy;
+use zbus::zvariant::OwnedValue;
+
+/// Every MPRIS-compliant player owns a well-known name under this prefix on the session bus,
+/// e.g. `org.mpris.MediaPlayer2.spotify`.
+pub const BUS_NAME_PREFIX: &str = "org.mpris.MediaPlayer2.";
+
+/// Proxy for the mandatory `org.mpris.MediaPlayer2.Player` interface. Since several players can
+/// be present at once, callers build this per bus name rather than relying on `default_service`.
+#[dbus_proxy(
+    interface = "org.mpris.MediaPlayer2.Player",
+    default_path = "/org/mpris/MediaPlayer2"
+)]
+trait MediaPlayer2Player {
+    fn next(&self) -> zbus::Result<()>;
+    fn previous(&self) -> zbus::Result<()>;
+    fn play_pause(&self) -> zbus::Result<()>;
+
+    #[dbus_proxy(property)]
+    fn playback_status(&self) -> zbus::Result<String>;
+
+    #[dbus_proxy(property)]
+    fn metadata(&self) -> zbus::Result<HashMap<String, OwnedValue>>;
+}
+
+/// `PlaybackStatus` as defined by the MPRIS spec, collapsed to the cases we render differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackStatus {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+impl From<&str> for PlaybackStatus {
+    fn from(s: &str) -> Self {
+        match s {
+            "Playing" => PlaybackStatus::Playing,
+            "Paused" => PlaybackStatus::Paused,
+            _ => PlaybackStatus::Stopped,
+        }
+    }
+}
+
+/// Pull `xesam:artist` (a string array) and `xesam:title` (a string) out of a `Metadata` map.
+/// A missing artist becomes an empty string; a missing or empty title yields `None`.
+pub fn track_from_metadata(metadata: &HashMap<String, OwnedValue>) -> Option<(String, String)> {
+    let title = metadata
+        .get("xesam:title")
+        .and_then(|v| <String>::try_from(v.clone()).ok())
+        .filter(|s| !s.is_empty())?;
+
+    let artist = metadata
+        .get("xesam:artist")
+        .and_then(|v| <Vec<String>>::try_from(v.clone()).ok())
+        .map(|artists| artists.join(", "))
+        .unwrap_or_default();
+
+    Some((artist, title))
+}
+
+#[cfg(test)]
+mod tests {
+    use zbus::zvariant::Value;
+
+    use super::*;
+
+    fn metadata(pairs: &[(&str, Value)]) -> HashMap<String, OwnedValue> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), OwnedValue::try_from(v.clone()).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn playback_status_from_str() {
+        assert_eq!(PlaybackStatus::from("Playing"), PlaybackStatus::Playing);
+        assert_eq!(PlaybackStatus::from("Paused"), PlaybackStatus::Paused);
+        assert_eq!(PlaybackStatus::from("Stopped"), PlaybackStatus::Stopped);
+        // unknown/garbage values are treated as stopped rather than panicking
+        assert_eq!(PlaybackStatus::from("whatever"), PlaybackStatus::Stopped);
+    }
+
+    #[test]
+    fn track_from_metadata_with_artist_and_title() {
+        let m = metadata(&[
+            ("xesam:title", Value::from("Harder, Better, Faster, Stronger")),
+            ("xesam:artist", Value::from(vec!["Daft Punk".to_string()])),
+        ]);
+
+        assert_eq!(
+            track_from_metadata(&m),
+            Some((
+                "Daft Punk".to_owned(),
+                "Harder, Better, Faster, Stronger".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn track_from_metadata_joins_multiple_artists() {
+        let m = metadata(&[
+            ("xesam:title", Value::from("Outro")),
+            (
+                "xesam:artist",
+                Value::from(vec!["M83".to_string(), "Susanne Sundfør".to_string()]),
+            ),
+        ]);
+
+        assert_eq!(
+            track_from_metadata(&m),
+            Some(("M83, Susanne Sundfør".to_owned(), "Outro".to_owned()))
+        );
+    }
+
+    #[test]
+    fn track_from_metadata_without_artist() {
+        let m = metadata(&[("xesam:title", Value::from("Untitled"))]);
+        assert_eq!(
+            track_from_metadata(&m),
+            Some(("".to_owned(), "Untitled".to_owned()))
+        );
+    }
+
+    #[test]
+    fn track_from_metadata_without_title_is_none() {
+        let m = metadata(&[("xesam:artist", Value::from(vec!["Daft Punk".to_string()]))]);
+        assert_eq!(track_from_metadata(&m), None);
+    }
+
+    #[test]
+    fn track_from_metadata_empty_title_is_none() {
+        let m = metadata(&[("xesam:title", Value::from(""))]);
+        assert_eq!(track_from_metadata(&m), None);
+    }
+}
@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use async_stream::stream;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use log::{debug, warn};
+use zbus::Message;
+
+use super::{dbus_connection, BusType};
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Declares a single dbus signal subscription a `BarItem` wants to react to: the bus to connect
+/// to, the well-known name owning the object, and the object/interface/signal to watch - e.g.
+/// `PropertiesChanged` on `org.freedesktop.DBus.Properties`, or a bespoke signal like
+/// NetworkManager's `StateChanged`.
+#[derive(Debug, Clone)]
+pub struct SubscriptionSpec {
+    pub bus: BusType,
+    pub service: String,
+    pub path: String,
+    pub interface: String,
+    pub signal: String,
+}
+
+impl SubscriptionSpec {
+    pub fn new(
+        bus: BusType,
+        service: impl Into<String>,
+        path: impl Into<String>,
+        interface: impl Into<String>,
+        signal: impl Into<String>,
+    ) -> SubscriptionSpec {
+        SubscriptionSpec {
+            bus,
+            service: service.into(),
+            path: path.into(),
+            interface: interface.into(),
+            signal: signal.into(),
+        }
+    }
+}
+
+/// One signal message received for one of the [`SubscriptionSpec`]s passed to [`subscribe`],
+/// identified by its index in that slice
+#[derive(Debug, Clone)]
+pub struct DbusEvent {
+    pub spec_idx: usize,
+    pub message: Message,
+}
+
+/// Subscribe to every `spec`, returning a single merged stream of [`DbusEvent`]s. A `BarItem`'s
+/// `start` loop can then `tokio::select!` over this one stream plus its own refresh interval,
+/// instead of hand-rolling a connection + a `select!` per signal.
+///
+/// If a spec's connection or proxy drops - the service disappeared, the bus itself restarted -
+/// it's transparently re-established with exponential backoff; the merged stream never ends on
+/// its own, it just goes quiet until the service reappears.
+pub fn subscribe(specs: Vec<SubscriptionSpec>) -> BoxStream<'static, DbusEvent> {
+    let streams = specs
+        .into_iter()
+        .enumerate()
+        .map(|(spec_idx, spec)| watch_one(spec_idx, spec))
+        .collect::<Vec<_>>();
+
+    futures::stream::select_all(streams).boxed()
+}
+
+fn watch_one(spec_idx: usize, spec: SubscriptionSpec) -> BoxStream<'static, DbusEvent> {
+    stream! {
+        let mut backoff = INITIAL_BACKOFF;
+        loop {
+            if let Ok(connection) = dbus_connection(spec.bus).await {
+                let proxy = zbus::Proxy::new(
+                    &connection,
+                    spec.service.clone(),
+                    spec.path.clone(),
+                    spec.interface.clone(),
+                )
+                .await;
+
+                if let Ok(proxy) = proxy {
+                    if let Ok(mut signals) = proxy.receive_signal(spec.signal.clone()).await {
+                        // connected: reset the backoff and forward signals until the stream
+                        // dries up (service/bus gone)
+                        backoff = INITIAL_BACKOFF;
+                        while let Some(message) = signals.next().await {
+                            yield DbusEvent {
+                                spec_idx,
+                                message: (*message).clone(),
+                            };
+                        }
+                        debug!(
+                            "dbus subscription to {}{} ({}) dried up, reconnecting",
+                            spec.service, spec.path, spec.signal
+                        );
+                    } else {
+                        warn!(
+                            "failed to subscribe to {} on {}{} ({}), backing off {:?}",
+                            spec.signal, spec.service, spec.path, spec.interface, backoff
+                        );
+                    }
+                } else {
+                    warn!(
+                        "failed to build a dbus proxy for {}{} ({}), backing off {:?}",
+                        spec.service, spec.path, spec.interface, backoff
+                    );
+                }
+            } else {
+                warn!(
+                    "failed to connect to the {:?} bus for {}, backing off {:?}",
+                    spec.bus, spec.service, backoff
+                );
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = next_backoff(backoff);
+        }
+    }
+    .boxed()
+}
+
+/// Double `current`, capped at `MAX_BACKOFF`
+fn next_backoff(current: Duration) -> Duration {
+    (current * 2).min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_backoff_doubles_from_initial() {
+        assert_eq!(next_backoff(INITIAL_BACKOFF), INITIAL_BACKOFF * 2);
+    }
+
+    #[test]
+    fn next_backoff_caps_at_max() {
+        let mut backoff = INITIAL_BACKOFF;
+        for _ in 0..10 {
+            backoff = next_backoff(backoff);
+        }
+        assert_eq!(backoff, MAX_BACKOFF);
+    }
+
+    #[test]
+    fn next_backoff_of_max_is_still_max() {
+        assert_eq!(next_backoff(MAX_BACKOFF), MAX_BACKOFF);
+    }
+}
@@ -0,0 +1,45 @@
+use hex_color::HexColor;
+use serde_derive::{Deserialize, Serialize};
+
+/// One foreground/background colour pair, used for a single "slot" when cycling through the
+/// powerline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowerlineColor {
+    pub fg: HexColor,
+    pub bg: HexColor,
+}
+
+/// The separator glyph rendered between powerline segments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowerlineSeparator(char);
+
+impl PowerlineSeparator {
+    pub fn to_span(self) -> String {
+        self.0.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Theme {
+    pub bg: HexColor,
+    pub dim: HexColor,
+    pub red: HexColor,
+    pub green: HexColor,
+    pub yellow: HexColor,
+    pub orange: HexColor,
+    pub powerline_enable: bool,
+    pub powerline_separator: PowerlineSeparator,
+    pub powerline: Vec<PowerlineColor>,
+    /// Minimum WCAG contrast ratio (see `Bar::create_powerline`) that `dim`-coloured powerline
+    /// text must have against its background; `dim` falling below this is nudged towards
+    /// `bg`/white until it's met. Other text colours are left as they are. 4.5:1 is the WCAG AA
+    /// threshold for normal-sized text.
+    #[serde(default = "Theme::default_min_contrast")]
+    pub min_contrast: f64,
+}
+
+impl Theme {
+    fn default_min_contrast() -> f64 {
+        4.5
+    }
+}